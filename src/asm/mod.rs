@@ -0,0 +1,501 @@
+//! A two-pass assembler for LC-3 assembly source, producing the same
+//! big-endian object format (an origin word followed by code words) that
+//! `VM::load_program` already consumes.
+
+use crate::vm::ops::{OpCode, TRAP_GETC, TRAP_HALT, TRAP_IN, TRAP_OUT, TRAP_PUTS, TRAP_PUTSP};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum AsmError {
+    MissingOrig,
+    DuplicateLabel { line: usize, label: String },
+    UndefinedLabel { line: usize, label: String },
+    OffsetOutOfRange { line: usize, value: i32, bits: u32 },
+    MalformedOperand { line: usize, text: String },
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AsmError::MissingOrig => write!(f, "missing .ORIG directive"),
+            AsmError::DuplicateLabel { line, label } => {
+                write!(f, "line {line}: label `{label}` is already defined")
+            }
+            AsmError::UndefinedLabel { line, label } => {
+                write!(f, "line {line}: undefined label `{label}`")
+            }
+            AsmError::OffsetOutOfRange { line, value, bits } => {
+                write!(f, "line {line}: value {value} does not fit in {bits} bits")
+            }
+            AsmError::MalformedOperand { line, text } => {
+                write!(f, "line {line}: malformed operand `{text}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+type SymbolTable = HashMap<String, u16>;
+
+struct SourceLine<'a> {
+    number: usize,
+    label: Option<&'a str>,
+    mnemonic: Option<&'a str>,
+    operands: Vec<&'a str>,
+}
+
+/// Assemble `source` into the big-endian object format `VM::load_program`
+/// expects: an origin word followed by the assembled code words.
+pub fn assemble(source: &str) -> Result<Vec<u16>, AsmError> {
+    let lines = parse_lines(source);
+    let origin = find_origin(&lines)?;
+    let symbols = first_pass(&lines, origin)?;
+    let code = second_pass(&lines, origin, &symbols)?;
+
+    let mut program = Vec::with_capacity(code.len() + 1);
+    program.push(origin);
+    program.extend(code);
+    Ok(program)
+}
+
+fn parse_lines(source: &str) -> Vec<SourceLine<'_>> {
+    let mut lines = Vec::new();
+
+    for (index, raw_line) in source.lines().enumerate() {
+        let number = index + 1;
+        let without_comment = raw_line.split(';').next().unwrap_or("").trim();
+        if without_comment.is_empty() {
+            continue;
+        }
+
+        let (head, rest) = split_once_whitespace(without_comment);
+        let (label, mnemonic, operand_field) = if is_mnemonic(head) {
+            (None, Some(head), rest)
+        } else {
+            let (mnemonic, operand_field) = split_once_whitespace(rest);
+            let mnemonic = if mnemonic.is_empty() { None } else { Some(mnemonic) };
+            (Some(head), mnemonic, operand_field)
+        };
+
+        let operands = split_operands(mnemonic, operand_field);
+        let is_end = mnemonic.map(|m| m.eq_ignore_ascii_case(".end")).unwrap_or(false);
+
+        lines.push(SourceLine { number, label, mnemonic, operands });
+
+        if is_end {
+            break;
+        }
+    }
+
+    lines
+}
+
+fn split_once_whitespace(text: &str) -> (&str, &str) {
+    match text.split_once(char::is_whitespace) {
+        Some((head, tail)) => (head, tail.trim()),
+        None => (text, ""),
+    }
+}
+
+fn split_operands<'a>(mnemonic: Option<&str>, field: &'a str) -> Vec<&'a str> {
+    if field.is_empty() {
+        return Vec::new();
+    }
+    if mnemonic.map(|m| m.eq_ignore_ascii_case(".stringz")).unwrap_or(false) {
+        return vec![field.trim()];
+    }
+    field.split(',').map(str::trim).filter(|s| !s.is_empty()).collect()
+}
+
+fn is_mnemonic(token: &str) -> bool {
+    matches!(
+        token.to_ascii_uppercase().as_str(),
+        "ADD" | "AND"
+            | "NOT"
+            | "BR"
+            | "BRN"
+            | "BRZ"
+            | "BRP"
+            | "BRNZ"
+            | "BRNP"
+            | "BRZP"
+            | "BRNZP"
+            | "JMP"
+            | "JSR"
+            | "JSRR"
+            | "LD"
+            | "LDI"
+            | "LDR"
+            | "LEA"
+            | "ST"
+            | "STI"
+            | "STR"
+            | "TRAP"
+            | "RTI"
+            | "GETC"
+            | "OUT"
+            | "PUTS"
+            | "IN"
+            | "PUTSP"
+            | "HALT"
+            | ".ORIG"
+            | ".FILL"
+            | ".BLKW"
+            | ".STRINGZ"
+            | ".END"
+    )
+}
+
+fn find_origin(lines: &[SourceLine]) -> Result<u16, AsmError> {
+    let orig_line = lines
+        .iter()
+        .find(|line| line.mnemonic.map(|m| m.eq_ignore_ascii_case(".orig")).unwrap_or(false))
+        .ok_or(AsmError::MissingOrig)?;
+
+    let operand = orig_line
+        .operands
+        .first()
+        .ok_or_else(|| malformed(orig_line, ".ORIG requires an address"))?;
+
+    parse_numeric_literal(operand).ok_or_else(|| malformed(orig_line, operand))
+}
+
+fn first_pass(lines: &[SourceLine], origin: u16) -> Result<SymbolTable, AsmError> {
+    let mut symbols = SymbolTable::new();
+    let mut counter = origin;
+
+    for line in lines {
+        if let Some(label) = line.label {
+            if symbols.insert(label.to_string(), counter).is_some() {
+                return Err(AsmError::DuplicateLabel {
+                    line: line.number,
+                    label: label.to_string(),
+                });
+            }
+        }
+
+        counter = counter.wrapping_add(word_count(line)?);
+    }
+
+    Ok(symbols)
+}
+
+fn second_pass(
+    lines: &[SourceLine],
+    origin: u16,
+    symbols: &SymbolTable,
+) -> Result<Vec<u16>, AsmError> {
+    let mut code = Vec::new();
+    let mut counter = origin;
+
+    for line in lines {
+        let Some(mnemonic) = line.mnemonic else {
+            continue;
+        };
+        let upper = mnemonic.to_ascii_uppercase();
+
+        match upper.as_str() {
+            ".ORIG" => {}
+            ".END" => break,
+            ".FILL" => {
+                let operand = line
+                    .operands
+                    .first()
+                    .ok_or_else(|| malformed(line, ".FILL requires a value"))?;
+                code.push(resolve_fill_value(operand, symbols, line)?);
+            }
+            ".BLKW" => {
+                let count = line
+                    .operands
+                    .first()
+                    .and_then(|op| parse_numeric_literal(op))
+                    .ok_or_else(|| malformed(line, ".BLKW requires a word count"))?;
+                code.extend(std::iter::repeat_n(0, count as usize));
+            }
+            ".STRINGZ" => {
+                let text = line.operands.first().copied().unwrap_or("");
+                let decoded =
+                    decode_string_literal(text).ok_or_else(|| malformed(line, text))?;
+                code.extend(decoded.chars().map(|c| c as u16));
+                code.push(0);
+            }
+            _ => code.push(encode_instruction(&upper, &line.operands, counter, symbols, line)?),
+        }
+
+        counter = counter.wrapping_add(word_count(line)?);
+    }
+
+    Ok(code)
+}
+
+/// How many words `line` occupies in the final image.
+fn word_count(line: &SourceLine) -> Result<u16, AsmError> {
+    match line.mnemonic {
+        None => Ok(0),
+        Some(m) if m.eq_ignore_ascii_case(".orig") || m.eq_ignore_ascii_case(".end") => Ok(0),
+        Some(m) if m.eq_ignore_ascii_case(".blkw") => line
+            .operands
+            .first()
+            .and_then(|op| parse_numeric_literal(op))
+            .ok_or_else(|| malformed(line, ".BLKW requires a word count")),
+        Some(m) if m.eq_ignore_ascii_case(".stringz") => {
+            let text = line.operands.first().copied().unwrap_or("");
+            let decoded = decode_string_literal(text).ok_or_else(|| malformed(line, text))?;
+            Ok(decoded.chars().count() as u16 + 1)
+        }
+        Some(_) => Ok(1),
+    }
+}
+
+fn decode_string_literal(raw: &str) -> Option<String> {
+    let inner = raw.strip_prefix('"')?.strip_suffix('"')?;
+    let mut result = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => result.push('\n'),
+            't' => result.push('\t'),
+            '0' => result.push('\0'),
+            other => result.push(other),
+        }
+    }
+
+    Some(result)
+}
+
+fn resolve_fill_value(
+    operand: &str,
+    symbols: &SymbolTable,
+    line: &SourceLine,
+) -> Result<u16, AsmError> {
+    if let Some(value) = parse_numeric_literal(operand) {
+        return Ok(value);
+    }
+    symbols.get(operand).copied().ok_or_else(|| AsmError::UndefinedLabel {
+        line: line.number,
+        label: operand.to_string(),
+    })
+}
+
+fn encode_instruction(
+    upper: &str,
+    operands: &[&str],
+    instr_addr: u16,
+    symbols: &SymbolTable,
+    line: &SourceLine,
+) -> Result<u16, AsmError> {
+    let next_pc = instr_addr.wrapping_add(1);
+
+    let reg = |index: usize| -> Result<u16, AsmError> {
+        let token = operands.get(index).copied().unwrap_or("<missing>");
+        parse_register(token).ok_or_else(|| malformed(line, token))
+    };
+
+    let pc_offset = |operand: &str, bits: u32| -> Result<u16, AsmError> {
+        let offset = if let Some(literal) = parse_numeric_literal(operand) {
+            literal as i16 as i32
+        } else {
+            let target = symbols.get(operand).copied().ok_or_else(|| AsmError::UndefinedLabel {
+                line: line.number,
+                label: operand.to_string(),
+            })?;
+            target.wrapping_sub(next_pc) as i16 as i32
+        };
+        check_fits_signed(offset, bits, line)?;
+        Ok((offset as u16) & mask(bits))
+    };
+
+    if let Some(nzp) = branch_condition_bits(upper) {
+        let operand = operands.first().copied().ok_or_else(|| malformed(line, "<missing>"))?;
+        let offset = pc_offset(operand, 9)?;
+        return Ok(((OpCode::BR as u16) << 12) | (nzp << 9) | offset);
+    }
+
+    match upper {
+        "ADD" | "AND" => {
+            let op_bits = if upper == "ADD" { OpCode::ADD as u16 } else { OpCode::AND as u16 };
+            let dr = reg(0)?;
+            let sr1 = reg(1)?;
+            let third = operands.get(2).copied().ok_or_else(|| malformed(line, "<missing>"))?;
+            let (imm_flag, low_bits) = match parse_register(third) {
+                Some(sr2) => (0u16, sr2),
+                None => {
+                    let value = parse_numeric_literal(third)
+                        .ok_or_else(|| malformed(line, third))? as i16 as i32;
+                    check_fits_signed(value, 5, line)?;
+                    (1u16, (value as u16) & 0x1F)
+                }
+            };
+            Ok((op_bits << 12) | (dr << 9) | (sr1 << 6) | (imm_flag << 5) | low_bits)
+        }
+        "NOT" => Ok(((OpCode::NOT as u16) << 12) | (reg(0)? << 9) | (reg(1)? << 6) | 0x3F),
+        "JMP" => Ok(((OpCode::JMP as u16) << 12) | (reg(0)? << 6)),
+        "JSRR" => Ok(((OpCode::JSR as u16) << 12) | (reg(0)? << 6)),
+        "JSR" => {
+            let operand = operands.first().copied().ok_or_else(|| malformed(line, "<missing>"))?;
+            Ok(((OpCode::JSR as u16) << 12) | (1 << 11) | pc_offset(operand, 11)?)
+        }
+        "LD" | "LDI" | "LEA" => {
+            let op_bits = match upper {
+                "LD" => OpCode::LD as u16,
+                "LDI" => OpCode::LDI as u16,
+                _ => OpCode::LEA as u16,
+            };
+            let operand = operands.get(1).copied().ok_or_else(|| malformed(line, "<missing>"))?;
+            Ok((op_bits << 12) | (reg(0)? << 9) | pc_offset(operand, 9)?)
+        }
+        "ST" | "STI" => {
+            let op_bits = if upper == "ST" { OpCode::ST as u16 } else { OpCode::STI as u16 };
+            let operand = operands.get(1).copied().ok_or_else(|| malformed(line, "<missing>"))?;
+            Ok((op_bits << 12) | (reg(0)? << 9) | pc_offset(operand, 9)?)
+        }
+        "LDR" | "STR" => {
+            let op_bits = if upper == "LDR" { OpCode::LDR as u16 } else { OpCode::STR as u16 };
+            let offset_token =
+                operands.get(2).copied().ok_or_else(|| malformed(line, "<missing>"))?;
+            let value = parse_numeric_literal(offset_token)
+                .ok_or_else(|| malformed(line, offset_token))? as i16 as i32;
+            check_fits_signed(value, 6, line)?;
+            Ok((op_bits << 12) | (reg(0)? << 9) | (reg(1)? << 6) | ((value as u16) & 0x3F))
+        }
+        "RTI" => Ok((OpCode::RTI as u16) << 12),
+        "TRAP" => {
+            let operand = operands.first().copied().ok_or_else(|| malformed(line, "<missing>"))?;
+            let vector = parse_numeric_literal(operand).ok_or_else(|| malformed(line, operand))?;
+            Ok(((OpCode::TRAP as u16) << 12) | (vector & 0xFF))
+        }
+        "GETC" => Ok(((OpCode::TRAP as u16) << 12) | TRAP_GETC),
+        "OUT" => Ok(((OpCode::TRAP as u16) << 12) | TRAP_OUT),
+        "PUTS" => Ok(((OpCode::TRAP as u16) << 12) | TRAP_PUTS),
+        "IN" => Ok(((OpCode::TRAP as u16) << 12) | TRAP_IN),
+        "PUTSP" => Ok(((OpCode::TRAP as u16) << 12) | TRAP_PUTSP),
+        "HALT" => Ok(((OpCode::TRAP as u16) << 12) | TRAP_HALT),
+        other => Err(malformed(line, other)),
+    }
+}
+
+fn branch_condition_bits(upper: &str) -> Option<u16> {
+    let suffix = upper.strip_prefix("BR")?;
+    if suffix.is_empty() {
+        return Some(0b111);
+    }
+
+    let mut bits = 0u16;
+    for c in suffix.chars() {
+        bits |= match c {
+            'N' => 0b100,
+            'Z' => 0b010,
+            'P' => 0b001,
+            _ => return None,
+        };
+    }
+    Some(bits)
+}
+
+fn parse_register(token: &str) -> Option<u16> {
+    let bytes = token.as_bytes();
+    if bytes.len() == 2 && (bytes[0] == b'R' || bytes[0] == b'r') {
+        (bytes[1] as char).to_digit(10).filter(|&r| r <= 7).map(|r| r as u16)
+    } else {
+        None
+    }
+}
+
+fn parse_numeric_literal(token: &str) -> Option<u16> {
+    let token = token.trim();
+    if let Some(hex) = token.strip_prefix('x').or_else(|| token.strip_prefix('X')) {
+        return u16::from_str_radix(hex, 16).ok();
+    }
+    if let Some(hex) = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")) {
+        return u16::from_str_radix(hex, 16).ok();
+    }
+    if let Some(dec) = token.strip_prefix('#') {
+        return dec.parse::<i32>().ok().map(|v| v as u16);
+    }
+    token.parse::<i32>().ok().map(|v| v as u16)
+}
+
+fn check_fits_signed(value: i32, bits: u32, line: &SourceLine) -> Result<(), AsmError> {
+    let min = -(1 << (bits - 1));
+    let max = (1 << (bits - 1)) - 1;
+    if value < min || value > max {
+        return Err(AsmError::OffsetOutOfRange { line: line.number, value, bits });
+    }
+    Ok(())
+}
+
+fn mask(bits: u32) -> u16 {
+    ((1u32 << bits) - 1) as u16
+}
+
+fn malformed(line: &SourceLine, text: &str) -> AsmError {
+    AsmError::MalformedOperand { line: line.number, text: text.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assembles_add_in_register_and_immediate_form() {
+        let source = ".ORIG x3000\nADD R0, R1, R2\nADD R0, R1, #-1\nHALT\n.END\n";
+        let program = assemble(source).unwrap();
+        assert_eq!(program, vec![0x3000, 0x1042, 0x107F, 0xF025]);
+    }
+
+    #[test]
+    fn resolves_branch_labels_to_pc_relative_offsets() {
+        let source = ".ORIG x3000\nLOOP ADD R0, R0, #-1\nBRp LOOP\nHALT\n.END\n";
+        let program = assemble(source).unwrap();
+        assert_eq!(program, vec![0x3000, 0x103F, 0x03FE, 0xF025]);
+    }
+
+    #[test]
+    fn undefined_label_is_reported_with_line_number() {
+        let source = ".ORIG x3000\nBR MISSING\n.END\n";
+        let err = assemble(source).unwrap_err();
+        assert!(matches!(err, AsmError::UndefinedLabel { line: 2, .. }));
+    }
+
+    #[test]
+    fn duplicate_label_is_rejected() {
+        let source = ".ORIG x3000\nLOOP ADD R0, R0, #1\nLOOP ADD R0, R0, #1\n.END\n";
+        let err = assemble(source).unwrap_err();
+        assert!(matches!(err, AsmError::DuplicateLabel { line: 3, .. }));
+    }
+
+    #[test]
+    fn offset_out_of_range_is_rejected() {
+        let source = ".ORIG x3000\nADD R0, R0, #16\n.END\n";
+        let err = assemble(source).unwrap_err();
+        assert!(matches!(err, AsmError::OffsetOutOfRange { bits: 5, .. }));
+    }
+
+    #[test]
+    fn round_trips_through_the_disassembler() {
+        let source = ".ORIG x3000\nADD R0, R1, R2\nNOT R0, R0\nBRnzp #-1\nHALT\n.END\n";
+        let program = assemble(source).unwrap();
+        let origin = program[0];
+        let lines: Vec<String> = program[1..]
+            .iter()
+            .enumerate()
+            .map(|(i, &word)| crate::disasm::disassemble(origin.wrapping_add(i as u16), word))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "ADD R0, R1, R2".to_string(),
+                "NOT R0, R0".to_string(),
+                "BRnzp #-1".to_string(),
+                "TRAP x25 (HALT)".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! Turns a fetched instruction word back into readable LC-3 assembly text,
+//! reusing `OpCode::from_u16` and the same field-extraction logic as the
+//! `VM`'s `*_op` methods so decoding stays in one place conceptually.
+
+use crate::vm::ops::{OpCode, TRAP_GETC, TRAP_HALT, TRAP_IN, TRAP_OUT, TRAP_PUTS, TRAP_PUTSP};
+
+/// Disassemble `instr`, which was fetched from `addr`, into one line of
+/// LC-3 assembly text. PC-relative operands of `LD`, `LDI`, `LEA`, `ST`,
+/// `STI` and `JSR` are resolved to the absolute target address they refer
+/// to; branch offsets are shown as the signed offset itself (`BRp #3`).
+pub fn disassemble(addr: u16, instr: u16) -> String {
+    let op_code = instr >> 12;
+    let r0 = (instr >> 9) & 0x7;
+    let r1 = (instr >> 6) & 0x7;
+
+    match OpCode::from_u16(op_code) {
+        Some(OpCode::ADD) | Some(OpCode::AND) => {
+            let mnemonic = if op_code == OpCode::ADD as u16 { "ADD" } else { "AND" };
+            let imm_flag = (instr >> 5) & 0x1;
+            if imm_flag != 0 {
+                let imm5 = sign_extend(instr & 0x1F, 5) as i16;
+                format!("{mnemonic} R{r0}, R{r1}, #{imm5}")
+            } else {
+                let r2 = instr & 0x7;
+                format!("{mnemonic} R{r0}, R{r1}, R{r2}")
+            }
+        }
+        Some(OpCode::NOT) => format!("NOT R{r0}, R{r1}"),
+        Some(OpCode::BR) => {
+            let cond = (instr >> 9) & 0x7;
+            let offset = sign_extend(instr & 0x1FF, 9) as i16;
+            // `nzp` = 0 never branches; render it distinctly from a bare
+            // `BR`, which the assembler takes to mean unconditional (nzp=111).
+            let mnemonic = if cond == 0 { "NOP".to_string() } else { format!("BR{}", branch_suffix(cond)) };
+            format!("{mnemonic} #{offset}")
+        }
+        Some(OpCode::JMP) => {
+            if r1 == 7 { "RET".to_string() } else { format!("JMP R{r1}") }
+        }
+        Some(OpCode::JSR) => {
+            let long_flag = (instr >> 11) & 0x1;
+            if long_flag != 0 {
+                let offset = sign_extend(instr & 0x7FF, 11);
+                format!("JSR x{:04X}", target(addr, offset))
+            } else {
+                format!("JSRR R{r1}")
+            }
+        }
+        Some(OpCode::LD) => format!("LD R{r0}, x{:04X}", pc_target(addr, instr)),
+        Some(OpCode::LDI) => format!("LDI R{r0}, x{:04X}", pc_target(addr, instr)),
+        Some(OpCode::LEA) => format!("LEA R{r0}, x{:04X}", pc_target(addr, instr)),
+        Some(OpCode::ST) => format!("ST R{r0}, x{:04X}", pc_target(addr, instr)),
+        Some(OpCode::STI) => format!("STI R{r0}, x{:04X}", pc_target(addr, instr)),
+        Some(OpCode::LDR) => {
+            let offset = sign_extend(instr & 0x3F, 6) as i16;
+            format!("LDR R{r0}, R{r1}, #{offset}")
+        }
+        Some(OpCode::STR) => {
+            let offset = sign_extend(instr & 0x3F, 6) as i16;
+            format!("STR R{r0}, R{r1}, #{offset}")
+        }
+        Some(OpCode::TRAP) => {
+            let vector = instr & 0xFF;
+            match trap_alias(vector) {
+                Some(alias) => format!("TRAP x{:02X} ({alias})", vector),
+                None => format!("TRAP x{:02X}", vector),
+            }
+        }
+        Some(OpCode::RTI) => "RTI".to_string(),
+        Some(OpCode::RES) => format!(".FILL x{:04X}", instr),
+        None => format!(".FILL x{:04X}", instr),
+    }
+}
+
+/// Resolve a 9-bit PC-relative load/store/LEA offset (bits 8-0 of `instr`)
+/// to the absolute address it targets.
+fn pc_target(addr: u16, instr: u16) -> u16 {
+    target(addr, sign_extend(instr & 0x1FF, 9))
+}
+
+/// The PC-relative target of `offset` fetched from `addr`: `addr + 1 + offset`.
+fn target(addr: u16, offset: u16) -> u16 {
+    addr.wrapping_add(1).wrapping_add(offset)
+}
+
+fn branch_suffix(flags: u16) -> String {
+    let mut suffix = String::new();
+    if flags & 0b100 != 0 {
+        suffix.push('n');
+    }
+    if flags & 0b010 != 0 {
+        suffix.push('z');
+    }
+    if flags & 0b001 != 0 {
+        suffix.push('p');
+    }
+    suffix
+}
+
+fn trap_alias(vector: u16) -> Option<&'static str> {
+    match vector {
+        TRAP_GETC => Some("GETC"),
+        TRAP_OUT => Some("OUT"),
+        TRAP_PUTS => Some("PUTS"),
+        TRAP_IN => Some("IN"),
+        TRAP_PUTSP => Some("PUTSP"),
+        TRAP_HALT => Some("HALT"),
+        _ => None,
+    }
+}
+
+fn sign_extend(x: u16, bit_count: u16) -> u16 {
+    if (x >> (bit_count - 1)) & 1 != 0 {
+        x | (0xFFFF << bit_count)
+    } else {
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disassembles_add_immediate() {
+        assert_eq!(disassemble(0x3000, 0x107F), "ADD R0, R1, #-1");
+    }
+
+    #[test]
+    fn disassembles_unconditional_branch_with_full_suffix() {
+        assert_eq!(disassemble(0x3000, 0x0FFC), "BRnzp #-4");
+    }
+
+    #[test]
+    fn disassembles_conditional_branch() {
+        assert_eq!(disassemble(0x3000, 0x0203), "BRp #3");
+    }
+
+    #[test]
+    fn disassembles_never_taken_branch_as_nop() {
+        assert_eq!(disassemble(0x3000, 0x0003), "NOP #3");
+    }
+
+    #[test]
+    fn disassembles_ldi_to_resolved_target_address() {
+        // x0FF2 is 15 words before x1001 (the PC after fetching at x1000),
+        // well within LDI's 9-bit signed offset range.
+        let offset = 0x0FF2u16.wrapping_sub(0x1001);
+        let instr = ((OpCode::LDI as u16) << 12) | (1 << 9) | (offset & 0x1FF);
+        assert_eq!(disassemble(0x1000, instr), "LDI R1, x0FF2");
+    }
+
+    #[test]
+    fn disassembles_halt_trap_with_alias() {
+        assert_eq!(disassemble(0x3000, 0xF025), "TRAP x25 (HALT)");
+    }
+}
@@ -0,0 +1,4 @@
+pub mod asm;
+pub mod disasm;
+pub mod terminal;
+pub mod vm;
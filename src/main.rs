@@ -7,8 +7,22 @@ use std::process;
 
 fn run() -> Result<(), VMError> {
     let args: Vec<String> = std::env::args().collect();
-    if args.len() < 2 {
-        eprintln!("Usage: {} [image-file1] ...", args[0]);
+
+    if args.get(1).map(String::as_str) == Some("asm") {
+        return run_assemble(&args[2..]);
+    }
+
+    if args.get(1).map(String::as_str) == Some("disasm") {
+        return run_disassemble(&args[2..]);
+    }
+
+    let debug = args.iter().any(|arg| arg == "--debug");
+    let image_paths: Vec<&String> = args[1..].iter().filter(|arg| *arg != "--debug").collect();
+
+    if image_paths.is_empty() {
+        eprintln!("Usage: {} [--debug] [image-file1] ...", args[0]);
+        eprintln!("       {} asm <input.asm> <output.obj>", args[0]);
+        eprintln!("       {} disasm <input.obj>", args[0]);
         process::exit(2);
     }
 
@@ -17,7 +31,7 @@ fn run() -> Result<(), VMError> {
     let mut vm = VM::new(terminal);
 
     // load all program images
-    for image_path in &args[1..] {
+    for image_path in image_paths {
         vm.load_program(image_path)?;
     }
 
@@ -31,7 +45,56 @@ fn run() -> Result<(), VMError> {
     });
 
     // run the vm
-    vm.run()
+    if debug { vm.run_debug() } else { vm.run() }
+}
+
+fn run_assemble(args: &[String]) -> Result<(), VMError> {
+    let (input_path, output_path) = match args {
+        [input, output] => (input, output),
+        _ => {
+            eprintln!("Usage: lc3vm asm <input.asm> <output.obj>");
+            process::exit(2);
+        }
+    };
+
+    let source = std::fs::read_to_string(input_path)?;
+    let program = match lc3_vm::asm::assemble(&source) {
+        Ok(program) => program,
+        Err(err) => {
+            eprintln!("assembly failed: {err}");
+            process::exit(1);
+        }
+    };
+
+    let mut bytes = Vec::with_capacity(program.len() * 2);
+    for word in program {
+        bytes.extend_from_slice(&word.to_be_bytes());
+    }
+    std::fs::write(output_path, bytes)?;
+    Ok(())
+}
+
+fn run_disassemble(args: &[String]) -> Result<(), VMError> {
+    let input_path = match args {
+        [input] => input,
+        _ => {
+            eprintln!("Usage: lc3vm disasm <input.obj>");
+            process::exit(2);
+        }
+    };
+
+    let bytes = std::fs::read(input_path)?;
+    let mut words = bytes.chunks_exact(2).map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]]));
+
+    let Some(origin) = words.next() else {
+        return Ok(());
+    };
+
+    for (offset, instr) in words.enumerate() {
+        let addr = origin.wrapping_add(offset as u16);
+        println!("x{:04X}  {}", addr, lc3_vm::disasm::disassemble(addr, instr));
+    }
+    Ok(())
 }
 
 fn main() {
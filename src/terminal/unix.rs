@@ -15,7 +15,7 @@ impl Terminal {
 
 impl TerminalInterface for Terminal {
     fn disable_input_buffering(&mut self) -> io::Result<()> {
-        let mut new_tio = self.original_tio.clone();
+        let mut new_tio = self.original_tio;
         new_tio.c_lflag &= !(ICANON | ECHO);
         tcsetattr(0, TCSANOW, &new_tio)?;
         Ok(())
@@ -0,0 +1,183 @@
+use super::interrupt::Interrupt;
+use std::io::{self, Read};
+
+/// A memory-mapped peripheral, registered with `Memory` over an address
+/// range so it can be attached without touching `Memory::read`/`write`.
+pub trait Device {
+    /// Handle a read from `addr`. Returning `None` falls through to the
+    /// backing memory cell, letting a device only intercept a subset of
+    /// its registered range.
+    fn read(&mut self, addr: u16) -> Option<u16>;
+    fn write(&mut self, addr: u16, val: u16);
+    /// Advance the device by one instruction cycle, optionally raising an
+    /// interrupt request.
+    fn tick(&mut self) -> Option<Interrupt>;
+}
+
+/// Control register bit enabling the timer; the remaining bits hold the
+/// reload interval, in instruction cycles.
+const TIMER_ENABLE: u16 = 1 << 15;
+const TIMER_INTERVAL_MASK: u16 = !TIMER_ENABLE;
+
+pub const TIMER_VECTOR: u8 = 0x81;
+pub const TIMER_PRIORITY: u8 = 4;
+
+/// A programmable timer: writing the control register sets the reload
+/// interval and the enable bit, and `tick` fires an interrupt exactly every
+/// `interval` cycles. An interval of `0` disables firing entirely, rather
+/// than raising an interrupt on every cycle.
+pub struct Timer {
+    control_addr: u16,
+    control: u16,
+    counter: u16,
+    vector: u8,
+    priority: u8,
+}
+
+impl Timer {
+    pub fn new(control_addr: u16, vector: u8, priority: u8) -> Self {
+        Self {
+            control_addr,
+            control: 0,
+            counter: 0,
+            vector,
+            priority,
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        self.control & TIMER_ENABLE != 0
+    }
+
+    fn reload_interval(&self) -> u16 {
+        self.control & TIMER_INTERVAL_MASK
+    }
+
+    /// Arm the counter so the next firing is exactly `reload_interval`
+    /// cycles away (firing happens when `counter` hits `0`, so start one
+    /// short of that).
+    fn arm(&mut self) {
+        self.counter = self.reload_interval().saturating_sub(1);
+    }
+}
+
+impl Device for Timer {
+    fn read(&mut self, addr: u16) -> Option<u16> {
+        (addr == self.control_addr).then_some(self.control)
+    }
+
+    fn write(&mut self, addr: u16, val: u16) {
+        if addr == self.control_addr {
+            self.control = val;
+            self.arm();
+        }
+    }
+
+    fn tick(&mut self) -> Option<Interrupt> {
+        if !self.enabled() || self.reload_interval() == 0 {
+            return None;
+        }
+
+        if self.counter == 0 {
+            self.arm();
+            return Some(Interrupt::new(self.vector, self.priority));
+        }
+
+        self.counter -= 1;
+        None
+    }
+}
+
+/// Keyboard status register bit set by the device once a character is
+/// latched into `KBDR`, and cleared when `KBDR` is read.
+pub(crate) const KBSR_READY: u16 = 1 << 15;
+/// Keyboard status register bit, writable by software, that enables the
+/// keyboard interrupt.
+const KBSR_IE: u16 = 1 << 14;
+
+pub const KEYBOARD_VECTOR: u8 = 0x80;
+pub const KEYBOARD_PRIORITY: u8 = 4;
+
+/// The keyboard: each `tick` samples stdin non-blockingly, latching any
+/// available character into `KBDR` and setting `KBSR`'s ready bit. If the
+/// interrupt-enable bit is set, a fresh character also raises an interrupt
+/// instead of requiring the program to poll the ready bit.
+pub struct Keyboard {
+    kbsr_addr: u16,
+    kbdr_addr: u16,
+    kbsr: u16,
+    kbdr: u16,
+    vector: u8,
+    priority: u8,
+}
+
+impl Keyboard {
+    pub fn new(kbsr_addr: u16, kbdr_addr: u16, vector: u8, priority: u8) -> Self {
+        Self {
+            kbsr_addr,
+            kbdr_addr,
+            kbsr: 0,
+            kbdr: 0,
+            vector,
+            priority,
+        }
+    }
+
+    fn interrupts_enabled(&self) -> bool {
+        self.kbsr & KBSR_IE != 0
+    }
+
+    /// Non-blocking check for a byte waiting on stdin.
+    fn key_available() -> bool {
+        use nix::sys::select::{FdSet, select};
+        use nix::sys::time::TimeVal;
+
+        let mut readfds = FdSet::new();
+        readfds.insert(0); // stdin
+
+        let mut timeout = TimeVal::new(0, 0);
+        match select(1, Some(&mut readfds), None, None, Some(&mut timeout)) {
+            Ok(n) => n > 0,
+            Err(_) => false,
+        }
+    }
+}
+
+impl Device for Keyboard {
+    fn read(&mut self, addr: u16) -> Option<u16> {
+        if addr == self.kbsr_addr {
+            Some(self.kbsr)
+        } else if addr == self.kbdr_addr {
+            let value = self.kbdr;
+            self.kbsr &= !KBSR_READY;
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    fn write(&mut self, addr: u16, val: u16) {
+        if addr == self.kbsr_addr {
+            // KBSR's ready bit is hardware-owned; only the interrupt-enable
+            // bit is software-writable.
+            self.kbsr = (self.kbsr & KBSR_READY) | (val & KBSR_IE);
+        }
+    }
+
+    fn tick(&mut self) -> Option<Interrupt> {
+        if self.kbsr & KBSR_READY != 0 || !Self::key_available() {
+            return None;
+        }
+
+        let mut byte = [0u8; 1];
+        if io::stdin().lock().read_exact(&mut byte).is_err() {
+            return None;
+        }
+
+        self.kbdr = byte[0] as u16;
+        self.kbsr |= KBSR_READY;
+
+        self.interrupts_enabled()
+            .then(|| Interrupt::new(self.vector, self.priority))
+    }
+}
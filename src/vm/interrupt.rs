@@ -0,0 +1,21 @@
+/// Exception vector used when a user-mode program tries to touch a
+/// supervisor-only resource (e.g. a device register) directly.
+pub const EXCEPTION_PRIVILEGE_VIOLATION: u8 = 0x00;
+
+/// Base address of the interrupt vector table; the handler address for
+/// vector `v` lives at `INTERRUPT_VECTOR_TABLE_BASE + v`.
+pub const INTERRUPT_VECTOR_TABLE_BASE: u16 = 0x0100;
+
+/// A pending interrupt request, raised by an exception or a device's
+/// `tick`, waiting to be accepted by the processor.
+#[derive(Clone, Copy, Debug)]
+pub struct Interrupt {
+    pub vector: u8,
+    pub priority: u8,
+}
+
+impl Interrupt {
+    pub fn new(vector: u8, priority: u8) -> Self {
+        Self { vector, priority }
+    }
+}
@@ -1,11 +1,37 @@
-use super::{memory::Memory, ops::OpCode, register::Register};
+use super::{
+    device::KBSR_READY,
+    interrupt::{EXCEPTION_PRIVILEGE_VIOLATION, INTERRUPT_VECTOR_TABLE_BASE, Interrupt},
+    memory::{MMIO_START, MR_KBDR, MR_KBSR, Memory},
+    ops::OpCode,
+    register::{
+        PSR_COND_MASK, PSR_PRIORITY_MASK, PSR_PRIORITY_SHIFT, PSR_PRIVILEGE, Register, SSP_INIT,
+        USP_INIT,
+    },
+};
 use crate::terminal::TerminalInterface;
+use std::collections::HashSet;
 use std::fs::File;
-use std::io::{self, Read, Write};
+use std::io::{self, BufRead, Read, Write};
+use std::{thread, time::Duration};
 
 pub struct VM<T: TerminalInterface> {
     memory: Memory,
     registers: [u16; Register::COUNT as usize],
+    /// Processor status register: privilege mode (bit 15), priority level
+    /// (bits 10-8) and the condition codes (bits 2-0, mirroring `COND`).
+    psr: u16,
+    /// Saved supervisor stack pointer, swapped into R6 on entry to
+    /// supervisor mode.
+    ssp: u16,
+    /// Saved user stack pointer, swapped into R6 on return to user mode.
+    usp: u16,
+    /// An interrupt request raised by a device's `tick`, waiting to be
+    /// accepted at the start of the next instruction cycle.
+    pending_interrupt: Option<Interrupt>,
+    /// PC addresses the debugger should stop execution at.
+    breakpoints: HashSet<u16>,
+    /// Whether the debugger should print each instruction as it executes.
+    trace: bool,
     terminal: T,
 }
 
@@ -24,9 +50,18 @@ impl From<io::Error> for VMError {
 
 impl<T: TerminalInterface> VM<T> {
     pub fn new(terminal: T) -> Self {
+        let mut registers = [0; Register::COUNT as usize];
+        registers[Register::R6 as usize] = USP_INIT;
+
         Self {
             memory: Memory::new(),
-            registers: [0; Register::COUNT as usize],
+            registers,
+            psr: PSR_PRIVILEGE,
+            ssp: SSP_INIT,
+            usp: USP_INIT,
+            pending_interrupt: None,
+            breakpoints: HashSet::new(),
+            trace: false,
             terminal,
         }
     }
@@ -59,38 +94,230 @@ impl<T: TerminalInterface> VM<T> {
         result
     }
 
-    fn execute(&mut self) -> Result<(), VMError> {
+    /// Drop into an interactive prompt instead of free-running `execute`.
+    /// Supports breakpoints, single-stepping, register/memory inspection
+    /// and a trace mode; repeating the last command on an empty line.
+    pub fn run_debug(&mut self) -> Result<(), VMError> {
+        self.reset();
+        let stdin = io::stdin();
+        let mut last_command = String::new();
+
+        loop {
+            print!("(lc3db) ");
+            io::stdout().flush()?;
+
+            let mut line = String::new();
+            if stdin.lock().read_line(&mut line)? == 0 {
+                break; // EOF
+            }
+
+            let command = match line.trim() {
+                "" => last_command.clone(),
+                trimmed => trimmed.to_string(),
+            };
+            if command.is_empty() {
+                continue;
+            }
+            last_command = command.clone();
+
+            if !self.run_debug_command(&command)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Run one debugger command. Returns `false` when the debugger should
+    /// exit.
+    fn run_debug_command(&mut self, command: &str) -> Result<bool, VMError> {
+        let mut parts = command.split_whitespace();
+        match parts.next() {
+            Some("step" | "s") if self.step()? => self.print_registers(),
+            Some("step" | "s") => {}
+            Some("continue" | "c") => self.debug_continue()?,
+            Some("break" | "b") => match parts.next().and_then(parse_addr) {
+                Some(addr) => {
+                    self.breakpoints.insert(addr);
+                    println!("breakpoint set at x{:04X}", addr);
+                }
+                None => eprintln!("usage: break <addr>"),
+            },
+            Some("delete" | "d") => match parts.next().and_then(parse_addr) {
+                Some(addr) => {
+                    self.breakpoints.remove(&addr);
+                    println!("breakpoint cleared at x{:04X}", addr);
+                }
+                None => eprintln!("usage: delete <addr>"),
+            },
+            Some("regs" | "r") => self.print_registers(),
+            Some("mem" | "m") => {
+                let start = parts.next().and_then(parse_addr);
+                let end = parts.next().and_then(parse_addr).or(start);
+                match (start, end) {
+                    (Some(start), Some(end)) => self.print_memory(start, end),
+                    _ => eprintln!("usage: mem <start> [end]"),
+                }
+            }
+            Some("set" | "w") => {
+                let addr = parts.next().and_then(parse_addr);
+                let value = parts.next().and_then(parse_addr);
+                match (addr, value) {
+                    (Some(addr), Some(value)) => {
+                        self.poke_memory(addr, value);
+                        println!("x{:04X} <- x{:04X}", addr, value);
+                    }
+                    _ => eprintln!("usage: set <addr> <value>"),
+                }
+            }
+            Some("trace" | "t") => {
+                self.trace = !self.trace;
+                println!("trace {}", if self.trace { "on" } else { "off" });
+            }
+            Some("quit" | "q") => return Ok(false),
+            Some("help" | "h") => self.print_debug_help(),
+            Some(other) => eprintln!("unknown command: {other} (type 'help')"),
+            None => {}
+        }
+        Ok(true)
+    }
+
+    fn debug_continue(&mut self) -> Result<(), VMError> {
+        loop {
+            if !self.step()? {
+                break;
+            }
+            if self.breakpoints.contains(&self.pc()) {
+                println!("breakpoint hit at x{:04X}", self.pc());
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn print_registers(&self) {
+        for i in 0..8 {
+            let register = Register::from_index(i).expect("0..8 are valid register indices");
+            println!("R{}: x{:04X}", i, self.register(register));
+        }
+        println!("PC: x{:04X}", self.pc());
+
+        let (n, z, p) = self.condition_flags();
+        println!(
+            "COND: {}{}{}",
+            if n { "N" } else { "-" },
+            if z { "Z" } else { "-" },
+            if p { "P" } else { "-" }
+        );
+    }
+
+    fn print_memory(&mut self, start: u16, end: u16) {
+        let mut addr = start;
+        loop {
+            println!("x{:04X}: x{:04X}", addr, self.peek_memory(addr));
+            if addr == end {
+                break;
+            }
+            addr = addr.wrapping_add(1);
+        }
+    }
+
+    fn print_debug_help(&self) {
+        println!("break|b <addr>        set a breakpoint");
+        println!("delete|d <addr>       clear a breakpoint");
+        println!("step|s                execute one instruction");
+        println!("continue|c            run until a breakpoint or HALT");
+        println!("regs|r                dump registers (PC and COND decoded)");
+        println!("mem|m <start> [end]   dump a range of memory");
+        println!("set|w <addr> <value>  write a memory cell");
+        println!("trace|t               toggle printing each instruction as it runs");
+        println!("quit|q                exit the debugger");
+        println!("(an empty line repeats the last command)");
+    }
+
+    /// Current value of the program counter.
+    pub fn pc(&self) -> u16 {
+        self.registers[Register::PC as usize]
+    }
+
+    /// Current value of a general-purpose or special register.
+    pub fn register(&self, register: Register) -> u16 {
+        self.registers[register as usize]
+    }
+
+    /// The condition codes as `(N, Z, P)`.
+    pub fn condition_flags(&self) -> (bool, bool, bool) {
+        let cond = self.registers[Register::COND as usize];
+        (
+            cond & super::register::FL_NEG != 0,
+            cond & super::register::FL_ZRO != 0,
+            cond & super::register::FL_POS != 0,
+        )
+    }
+
+    /// Read a memory cell, for inspection. Shares `Memory::read`'s
+    /// side effects on the keyboard status/data registers.
+    pub fn peek_memory(&mut self, addr: u16) -> u16 {
+        self.memory.read(addr)
+    }
+
+    /// Write a memory cell, for inspection/patching.
+    pub fn poke_memory(&mut self, addr: u16, value: u16) {
+        self.memory.write(addr, value);
+    }
+
+    fn reset(&mut self) {
         // set pc to starting position (0x3000 is the default)
         const PC_START: u16 = 0x3000;
         self.registers[Register::PC as usize] = PC_START;
 
         // set condition flag to z
         self.registers[Register::COND as usize] = super::register::FL_ZRO;
+        self.psr = (self.psr & !PSR_COND_MASK) | super::register::FL_ZRO;
+    }
 
-        let mut running = true;
-        while running {
-            // fetch
-            let pc = self.registers[Register::PC as usize];
-            self.registers[Register::PC as usize] += 1;
-            let instr = self.memory.read(pc);
-
-            // decode
-            let op_code = (instr >> 12) as u16;
-
-            // execute
-            match OpCode::from_u16(op_code) {
-                Some(op) => {
-                    if let OpCode::TRAP = op {
-                        if (instr & 0xFF) == super::ops::TRAP_HALT {
-                            running = false;
-                        }
-                    }
-                    self.execute_instruction(op, instr)?
-                }
-                None => return Err(VMError::InvalidOpCode(op_code)),
+    fn execute(&mut self) -> Result<(), VMError> {
+        self.reset();
+        while self.step()? {}
+        Ok(())
+    }
+
+    /// Run a single instruction cycle: let devices tick, accept a pending
+    /// interrupt if one outranks the current PL, then fetch/decode/execute
+    /// one instruction. Returns `false` once a `HALT` trap has executed.
+    pub fn step(&mut self) -> Result<bool, VMError> {
+        // let devices observe a cycle passing; they may raise an interrupt
+        for interrupt in self.memory.tick_devices() {
+            self.request_interrupt(interrupt);
+        }
+
+        // accept a pending device interrupt if it outranks the current PL
+        if let Some(interrupt) = self.pending_interrupt {
+            if self.accept_interrupt(interrupt) {
+                self.pending_interrupt = None;
             }
         }
-        Ok(())
+
+        // fetch
+        let pc = self.registers[Register::PC as usize];
+        self.registers[Register::PC as usize] += 1;
+        let instr = self.memory.read(pc);
+
+        if self.trace {
+            println!("x{:04X}: {}", pc, crate::disasm::disassemble(pc, instr));
+        }
+
+        // decode
+        let op_code = instr >> 12;
+
+        // execute
+        match OpCode::from_u16(op_code) {
+            Some(op) => {
+                let halted = matches!(op, OpCode::TRAP) && (instr & 0xFF) == super::ops::TRAP_HALT;
+                self.execute_instruction(op, instr)?;
+                Ok(!halted)
+            }
+            None => Err(VMError::InvalidOpCode(op_code)),
+        }
     }
 
     fn execute_instruction(&mut self, op: OpCode, instr: u16) -> Result<(), VMError> {
@@ -109,7 +336,136 @@ impl<T: TerminalInterface> VM<T> {
             OpCode::STI => self.store_indirect_op(instr),
             OpCode::STR => self.store_register_op(instr),
             OpCode::TRAP => self.trap_op(instr),
-            OpCode::RES | OpCode::RTI => Err(VMError::InvalidOpCode(op as u16)),
+            OpCode::RTI => self.rti_op(instr),
+            OpCode::RES => Err(VMError::InvalidOpCode(op as u16)),
+        }
+    }
+
+    fn rti_op(&mut self, _instr: u16) -> Result<(), VMError> {
+        if self.in_user_mode() {
+            self.exception(EXCEPTION_PRIVILEGE_VIOLATION);
+            return Ok(());
+        }
+
+        let pc = self.pop_supervisor_stack();
+        let psr = self.pop_supervisor_stack();
+
+        self.registers[Register::PC as usize] = pc;
+        self.registers[Register::COND as usize] = psr & PSR_COND_MASK;
+        self.restore_stack_for_psr(psr);
+        self.psr = psr;
+        Ok(())
+    }
+
+    /// True while the processor is executing in user mode.
+    fn in_user_mode(&self) -> bool {
+        self.psr & PSR_PRIVILEGE != 0
+    }
+
+    fn current_priority(&self) -> u8 {
+        ((self.psr & PSR_PRIORITY_MASK) >> PSR_PRIORITY_SHIFT) as u8
+    }
+
+    /// Record `interrupt` as pending, keeping whichever request has the
+    /// higher priority if one is already waiting.
+    fn request_interrupt(&mut self, interrupt: Interrupt) {
+        let outranks_pending = match self.pending_interrupt {
+            Some(pending) => interrupt.priority > pending.priority,
+            None => true,
+        };
+        if outranks_pending {
+            self.pending_interrupt = Some(interrupt);
+        }
+    }
+
+    /// Accept `interrupt` if its priority outranks the current PL, pushing
+    /// the old PSR and PC onto the supervisor stack and loading PC from the
+    /// interrupt vector table. Returns whether the interrupt was accepted.
+    fn accept_interrupt(&mut self, interrupt: Interrupt) -> bool {
+        if interrupt.priority <= self.current_priority() {
+            return false;
+        }
+
+        self.raise(interrupt.vector);
+        self.psr = (self.psr & !PSR_PRIORITY_MASK) | ((interrupt.priority as u16) << PSR_PRIORITY_SHIFT);
+        true
+    }
+
+    /// Raise a synchronous exception at `vector`. Unlike a device
+    /// interrupt this is never masked by the current PL.
+    fn exception(&mut self, vector: u8) {
+        self.raise(vector);
+    }
+
+    /// Shared tail of interrupt/exception delivery: save the old PSR and
+    /// PC on the supervisor stack, switch to supervisor mode, and load PC
+    /// from the interrupt vector table.
+    fn raise(&mut self, vector: u8) {
+        let old_psr = self.psr;
+        let old_pc = self.registers[Register::PC as usize];
+
+        self.enter_supervisor_mode();
+        self.push_supervisor_stack(old_psr);
+        self.push_supervisor_stack(old_pc);
+
+        let vector_addr = INTERRUPT_VECTOR_TABLE_BASE.wrapping_add(vector as u16);
+        self.registers[Register::PC as usize] = self.memory.read(vector_addr);
+    }
+
+    fn enter_supervisor_mode(&mut self) {
+        if self.in_user_mode() {
+            self.usp = self.registers[Register::R6 as usize];
+            self.registers[Register::R6 as usize] = self.ssp;
+            self.psr &= !PSR_PRIVILEGE;
+        }
+    }
+
+    /// Swap R6 back to the user stack if `psr` (about to become the live
+    /// PSR) indicates user mode. Must run before `self.psr` is overwritten.
+    fn restore_stack_for_psr(&mut self, psr: u16) {
+        if psr & PSR_PRIVILEGE != 0 {
+            self.ssp = self.registers[Register::R6 as usize];
+            self.registers[Register::R6 as usize] = self.usp;
+        }
+    }
+
+    fn push_supervisor_stack(&mut self, value: u16) {
+        self.registers[Register::R6 as usize] = self.registers[Register::R6 as usize].wrapping_sub(1);
+        let addr = self.registers[Register::R6 as usize];
+        self.memory.write(addr, value);
+    }
+
+    fn pop_supervisor_stack(&mut self) -> u16 {
+        let addr = self.registers[Register::R6 as usize];
+        self.registers[Register::R6 as usize] = self.registers[Register::R6 as usize].wrapping_add(1);
+        self.memory.read(addr)
+    }
+
+    /// Guard against user-mode code touching the device register region
+    /// directly. Raises a privilege-mode-violation exception and returns
+    /// `true` (meaning the caller must abort the access) when it does.
+    fn check_privileged_access(&mut self, addr: u16) -> bool {
+        if self.in_user_mode() && addr >= MMIO_START {
+            self.exception(EXCEPTION_PRIVILEGE_VIOLATION);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Block until the keyboard device has a character latched in `KBDR`,
+    /// then consume it. Goes through the device rather than reading stdin
+    /// directly so `GETC`/`IN` never race `Keyboard::tick`'s own
+    /// non-blocking stdin reads for the same bytes.
+    fn wait_for_key(&mut self) -> u16 {
+        loop {
+            for interrupt in self.memory.tick_devices() {
+                self.request_interrupt(interrupt);
+            }
+            if self.memory.read(MR_KBSR) & KBSR_READY != 0 {
+                return self.memory.read(MR_KBDR);
+            }
+            thread::sleep(Duration::from_millis(1));
         }
     }
 
@@ -192,6 +548,9 @@ impl<T: TerminalInterface> VM<T> {
         let r0 = (instr >> 9) & 0x7;
         let pc_offset = sign_extend(instr & 0x1FF, 9);
         let addr = self.registers[Register::PC as usize].wrapping_add(pc_offset);
+        if self.check_privileged_access(addr) {
+            return Ok(());
+        }
 
         self.registers[r0 as usize] = self.memory.read(addr);
         self.update_flags(r0);
@@ -204,6 +563,9 @@ impl<T: TerminalInterface> VM<T> {
         let indirect_addr = self
             .memory
             .read(self.registers[Register::PC as usize].wrapping_add(pc_offset));
+        if self.check_privileged_access(indirect_addr) {
+            return Ok(());
+        }
 
         self.registers[r0 as usize] = self.memory.read(indirect_addr);
         self.update_flags(r0);
@@ -216,6 +578,10 @@ impl<T: TerminalInterface> VM<T> {
         let offset = sign_extend(instr & 0x3F, 6);
 
         let addr = self.registers[r1 as usize].wrapping_add(offset);
+        if self.check_privileged_access(addr) {
+            return Ok(());
+        }
+
         self.registers[r0 as usize] = self.memory.read(addr);
         self.update_flags(r0);
         Ok(())
@@ -234,6 +600,9 @@ impl<T: TerminalInterface> VM<T> {
         let r0 = (instr >> 9) & 0x7;
         let pc_offset = sign_extend(instr & 0x1FF, 9);
         let addr = self.registers[Register::PC as usize].wrapping_add(pc_offset);
+        if self.check_privileged_access(addr) {
+            return Ok(());
+        }
 
         self.memory.write(addr, self.registers[r0 as usize]);
         Ok(())
@@ -245,6 +614,9 @@ impl<T: TerminalInterface> VM<T> {
         let indirect_addr = self
             .memory
             .read(self.registers[Register::PC as usize].wrapping_add(pc_offset));
+        if self.check_privileged_access(indirect_addr) {
+            return Ok(());
+        }
 
         self.memory
             .write(indirect_addr, self.registers[r0 as usize]);
@@ -257,6 +629,10 @@ impl<T: TerminalInterface> VM<T> {
         let offset = sign_extend(instr & 0x3F, 6);
 
         let addr = self.registers[r1 as usize].wrapping_add(offset);
+        if self.check_privileged_access(addr) {
+            return Ok(());
+        }
+
         self.memory.write(addr, self.registers[r0 as usize]);
         Ok(())
     }
@@ -268,9 +644,7 @@ impl<T: TerminalInterface> VM<T> {
 
         match instr & 0xFF {
             TRAP_GETC => {
-                let mut buffer = [0u8; 1];
-                io::stdin().read_exact(&mut buffer)?;
-                self.registers[Register::R0 as usize] = buffer[0] as u16;
+                self.registers[Register::R0 as usize] = self.wait_for_key();
                 self.update_flags(Register::R0 as u16);
             }
             TRAP_OUT => {
@@ -290,12 +664,10 @@ impl<T: TerminalInterface> VM<T> {
             TRAP_IN => {
                 print!("Enter a character: ");
                 io::stdout().flush()?;
-                let mut buffer = [0u8; 1];
-                io::stdin().read_exact(&mut buffer)?;
-                let char = buffer[0] as char;
-                print!("{}", char);
+                let key = self.wait_for_key();
+                print!("{}", (key & 0xFF) as u8 as char);
                 io::stdout().flush()?;
-                self.registers[Register::R0 as usize] = char as u16;
+                self.registers[Register::R0 as usize] = key;
                 self.update_flags(Register::R0 as u16);
             }
             TRAP_PUTSP => {
@@ -333,6 +705,7 @@ impl<T: TerminalInterface> VM<T> {
             super::register::FL_POS
         };
         self.registers[Register::COND as usize] = flag;
+        self.psr = (self.psr & !PSR_COND_MASK) | flag;
     }
 }
 
@@ -343,3 +716,10 @@ fn sign_extend(x: u16, bit_count: u16) -> u16 {
         x
     }
 }
+
+/// Parse a debugger address/value argument, accepting LC-3 style `x3000`
+/// or plain `0x3000`/`3000` hex notation.
+fn parse_addr(s: &str) -> Option<u16> {
+    let s = s.strip_prefix("0x").or_else(|| s.strip_prefix('x')).unwrap_or(s);
+    u16::from_str_radix(s, 16).ok()
+}
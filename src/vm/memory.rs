@@ -1,51 +1,86 @@
-use std::io::{self, Read};
+use super::device::{
+    Device, KEYBOARD_PRIORITY, KEYBOARD_VECTOR, Keyboard, TIMER_PRIORITY, TIMER_VECTOR, Timer,
+};
+use super::interrupt::Interrupt;
 
 pub const MEMORY_MAX: usize = 1 << 16; // 65536 locations
 
 // memory-mapped registers
 pub const MR_KBSR: u16 = 0xFE00; // keyboard status
 pub const MR_KBDR: u16 = 0xFE02; // keyboard data
+pub const MR_TIMER: u16 = 0xFE10; // timer control register
+
+// start of the memory-mapped I/O region; user-mode code may not access
+// addresses in this range directly (see `VM::check_privileged_access`)
+pub const MMIO_START: u16 = 0xFE00;
 
 pub struct Memory {
     cells: [u16; MEMORY_MAX],
+    devices: Vec<(u16, u16, Box<dyn Device>)>,
+}
+
+impl Default for Memory {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Memory {
     pub fn new() -> Self {
-        Self {
+        let mut memory = Self {
             cells: [0; MEMORY_MAX],
-        }
+            devices: Vec::new(),
+        };
+
+        memory.register_device(
+            MR_KBSR,
+            MR_KBDR,
+            Box::new(Keyboard::new(MR_KBSR, MR_KBDR, KEYBOARD_VECTOR, KEYBOARD_PRIORITY)),
+        );
+        memory.register_device(
+            MR_TIMER,
+            MR_TIMER,
+            Box::new(Timer::new(MR_TIMER, TIMER_VECTOR, TIMER_PRIORITY)),
+        );
+
+        memory
+    }
+
+    /// Attach a device, handling reads and writes to `start..=end`.
+    pub fn register_device(&mut self, start: u16, end: u16, device: Box<dyn Device>) {
+        self.devices.push((start, end, device));
     }
 
     pub fn write(&mut self, address: u16, value: u16) {
+        for (start, end, device) in self.devices.iter_mut() {
+            if (*start..=*end).contains(&address) {
+                device.write(address, value);
+                return;
+            }
+        }
         self.cells[address as usize] = value;
     }
 
     pub fn read(&mut self, address: u16) -> u16 {
-        if address == MR_KBSR {
-            if Self::check_key() {
-                self.cells[MR_KBSR as usize] = 1 << 15;
-                self.cells[MR_KBDR as usize] =
-                    io::stdin().bytes().next().and_then(|b| b.ok()).unwrap_or(0) as u16;
-            } else {
-                self.cells[MR_KBSR as usize] = 0;
+        for (start, end, device) in self.devices.iter_mut() {
+            if (*start..=*end).contains(&address) {
+                if let Some(value) = device.read(address) {
+                    return value;
+                }
+                break;
             }
         }
+
         self.cells[address as usize]
     }
 
-    fn check_key() -> bool {
-        use nix::sys::select::{FdSet, select};
-        use nix::sys::time::TimeVal;
-
-        let mut readfds = FdSet::new();
-        readfds.insert(0); // stdin
-
-        let mut timeout = TimeVal::new(0, 0);
-        match select(1, Some(&mut readfds), None, None, Some(&mut timeout)) {
-            Ok(n) => n > 0,
-            Err(_) => false,
-        }
+    /// Advance every registered device by one instruction cycle, collecting
+    /// any interrupts they raise.
+    pub fn tick_devices(&mut self) -> Vec<Interrupt> {
+        self.devices
+            .iter_mut()
+            .filter_map(|(_, _, device)| device.tick())
+            .collect()
     }
 
     pub fn load_image(&mut self, origin: u16, program: &[u16]) {
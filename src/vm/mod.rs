@@ -0,0 +1,6 @@
+pub mod device;
+pub mod interrupt;
+pub mod machine;
+pub mod memory;
+pub mod ops;
+pub mod register;
@@ -8,7 +8,7 @@ pub enum OpCode {
     AND,    // bitwise and
     LDR,    // load register
     STR,    // store register
-    RTI,    // unused
+    RTI,    // return from interrupt
     NOT,    // bitwise not
     LDI,    // load indirect
     STI,    // store indirect
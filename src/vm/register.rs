@@ -18,6 +18,16 @@ pub const FL_POS: u16 = 1 << 0; // positive
 pub const FL_ZRO: u16 = 1 << 1; // zero
 pub const FL_NEG: u16 = 1 << 2; // negative
 
+// processor status register (PSR) bit layout
+pub const PSR_PRIVILEGE: u16 = 1 << 15; // 0 = supervisor, 1 = user
+pub const PSR_PRIORITY_SHIFT: u16 = 8; // bits 10-8 hold the priority level
+pub const PSR_PRIORITY_MASK: u16 = 0x7 << PSR_PRIORITY_SHIFT;
+pub const PSR_COND_MASK: u16 = FL_POS | FL_ZRO | FL_NEG; // bits 2-0 mirror COND
+
+// default stack pointers (R6) for the two privilege modes
+pub const SSP_INIT: u16 = 0x3000; // supervisor stack, grows down from the OS image
+pub const USP_INIT: u16 = 0xFE00; // user stack, grows down into user memory
+
 impl Register {
     pub fn from_index(index: usize) -> Option<Self> {
         match index {